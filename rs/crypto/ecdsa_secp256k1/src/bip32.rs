@@ -0,0 +1,398 @@
+//! BIP-32 hierarchical deterministic key derivation.
+//!
+//! This lets callers derive a tree of related keys from a single seed (or
+//! from an existing extended key) without handling raw scalars themselves,
+//! and supports the BIP-32 "public derivation" trick of deriving child
+//! public keys without ever materializing the corresponding private keys.
+
+use crate::{KeyDecodingError, PrivateKey, PublicKey};
+use hmac::{Hmac, Mac};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{ProjectivePoint, Scalar};
+use sha2::Sha512;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// The index at and above which a BIP-32 child is "hardened", i.e. can only
+/// be derived from a private (not public) parent key.
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// Errors that can occur while deriving or decoding an extended key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExtendedKeyError {
+    /// Deriving this index produced an invalid child key; per BIP-32 the
+    /// caller should retry with the next index. This is vanishingly
+    /// unlikely (probability roughly `2^-127` per index).
+    InvalidDerivedKey,
+    /// A hardened index was requested for a public-only derivation.
+    HardenedDerivationRequiresPrivateKey,
+    /// The derivation path string was not of the form `m/44'/0'/0'/0/0`.
+    InvalidDerivationPath(String),
+    /// The extended key encoding could not be parsed.
+    InvalidEncoding(String),
+}
+
+impl std::fmt::Display for ExtendedKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidDerivedKey => write!(f, "derived key is invalid, retry with the next index"),
+            Self::HardenedDerivationRequiresPrivateKey => {
+                write!(f, "hardened child derivation requires a private key")
+            }
+            Self::InvalidDerivationPath(s) => write!(f, "invalid derivation path: {}", s),
+            Self::InvalidEncoding(s) => write!(f, "invalid extended key encoding: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for ExtendedKeyError {}
+
+impl From<KeyDecodingError> for ExtendedKeyError {
+    fn from(e: KeyDecodingError) -> Self {
+        Self::InvalidEncoding(e.to_string())
+    }
+}
+
+/// A BIP-32 extended private key: a `PrivateKey` plus the chain code
+/// needed to derive children from it.
+#[derive(Clone, Eq, PartialEq)]
+pub struct ExtendedPrivateKey {
+    key: PrivateKey,
+    chain_code: [u8; 32],
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+}
+
+/// A BIP-32 extended public key: a `PublicKey` plus the chain code needed
+/// to derive non-hardened children from it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ExtendedPublicKey {
+    key: PublicKey,
+    chain_code: [u8; 32],
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+}
+
+/// The four-byte mainnet version prefix for serialized extended private keys.
+const XPRV_VERSION: [u8; 4] = [0x04, 0x88, 0xAD, 0xE4];
+/// The four-byte mainnet version prefix for serialized extended public keys.
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+
+fn hmac_sha512(chain_code: &[u8; 32], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = HmacSha512::new_from_slice(chain_code).expect("HMAC accepts any key length");
+    mac.update(data);
+    let i = mac.finalize().into_bytes();
+
+    let mut i_l = [0u8; 32];
+    let mut i_r = [0u8; 32];
+    i_l.copy_from_slice(&i[..32]);
+    i_r.copy_from_slice(&i[32..]);
+    (i_l, i_r)
+}
+
+/// `RIPEMD160(SHA256(data))`, as used for BIP-32 key fingerprints.
+fn hash160(data: &[u8]) -> [u8; 20] {
+    use ripemd::Ripemd160;
+    use sha2::{Digest, Sha256};
+
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+fn fingerprint(compressed_pubkey: &[u8]) -> [u8; 4] {
+    let mut fp = [0u8; 4];
+    fp.copy_from_slice(&hash160(compressed_pubkey)[..4]);
+    fp
+}
+
+fn serialize_extended_key(
+    version: [u8; 4],
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+    chain_code: &[u8; 32],
+    key_data: &[u8],
+) -> String {
+    let mut payload = Vec::with_capacity(78);
+    payload.extend_from_slice(&version);
+    payload.push(depth);
+    payload.extend_from_slice(&parent_fingerprint);
+    payload.extend_from_slice(&child_number.to_be_bytes());
+    payload.extend_from_slice(chain_code);
+    payload.extend_from_slice(key_data);
+    bs58::encode(payload).with_check().into_string()
+}
+
+fn deserialize_extended_key(
+    s: &str,
+    expected_version: [u8; 4],
+) -> Result<(u8, [u8; 4], u32, [u8; 32], Vec<u8>), ExtendedKeyError> {
+    let payload = bs58::decode(s)
+        .with_check(None)
+        .into_vec()
+        .map_err(|e| ExtendedKeyError::InvalidEncoding(e.to_string()))?;
+    if payload.len() != 78 {
+        return Err(ExtendedKeyError::InvalidEncoding(format!(
+            "expected 78 bytes, got {}",
+            payload.len()
+        )));
+    }
+    if payload[0..4] != expected_version {
+        return Err(ExtendedKeyError::InvalidEncoding(
+            "unexpected version bytes".to_string(),
+        ));
+    }
+
+    let depth = payload[4];
+    let mut parent_fingerprint = [0u8; 4];
+    parent_fingerprint.copy_from_slice(&payload[5..9]);
+    let child_number = u32::from_be_bytes(payload[9..13].try_into().unwrap());
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&payload[13..45]);
+    let key_data = payload[45..78].to_vec();
+
+    Ok((depth, parent_fingerprint, child_number, chain_code, key_data))
+}
+
+/// Parses a BIP-32 path component such as `44'` or `0` into a raw index.
+fn parse_path_component(s: &str) -> Result<u32, ExtendedKeyError> {
+    let (digits, hardened) = match s.strip_suffix('\'').or_else(|| s.strip_suffix('h')) {
+        Some(digits) => (digits, true),
+        None => (s, false),
+    };
+    let index: u32 = digits
+        .parse()
+        .map_err(|_| ExtendedKeyError::InvalidDerivationPath(s.to_string()))?;
+    if index >= HARDENED_OFFSET {
+        return Err(ExtendedKeyError::InvalidDerivationPath(s.to_string()));
+    }
+    Ok(if hardened { index + HARDENED_OFFSET } else { index })
+}
+
+fn parse_path(path: &str) -> Result<Vec<u32>, ExtendedKeyError> {
+    let mut components = path.split('/');
+    match components.next() {
+        Some("m") => {}
+        _ => return Err(ExtendedKeyError::InvalidDerivationPath(path.to_string())),
+    }
+    components.map(parse_path_component).collect()
+}
+
+impl ExtendedPrivateKey {
+    /// Derives the BIP-32 master extended key from a seed, as specified by
+    /// `I = HMAC-SHA512("Bitcoin seed", seed)`. As with `derive_child`, an
+    /// `I_L` that is not a valid nonzero scalar is rejected rather than
+    /// silently wrapped, though this is vanishingly unlikely in practice.
+    pub fn derive_new_master_key(seed: &[u8]) -> Result<Self, ExtendedKeyError> {
+        let mut mac =
+            HmacSha512::new_from_slice(b"Bitcoin seed").expect("HMAC accepts any key length");
+        mac.update(seed);
+        let i = mac.finalize().into_bytes();
+
+        let secret = Option::<Scalar>::from(Scalar::from_repr(i[..32].into()))
+            .ok_or(ExtendedKeyError::InvalidDerivedKey)?;
+        if bool::from(secret.is_zero()) {
+            return Err(ExtendedKeyError::InvalidDerivedKey);
+        }
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&i[32..]);
+
+        Ok(Self {
+            key: PrivateKey::deserialize_sec1(&secret.to_bytes())
+                .expect("a nonzero scalar is a valid secret key"),
+            chain_code,
+            depth: 0,
+            parent_fingerprint: [0u8; 4],
+            child_number: 0,
+        })
+    }
+
+    /// The private key at this node of the tree.
+    pub fn private_key(&self) -> &PrivateKey {
+        &self.key
+    }
+
+    /// The chain code at this node of the tree.
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    /// The extended public key corresponding to this extended private key.
+    pub fn public_key(&self) -> ExtendedPublicKey {
+        ExtendedPublicKey {
+            key: self.key.public_key(),
+            chain_code: self.chain_code,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+        }
+    }
+
+    /// Serializes this key in the standard base58check `xprv` format.
+    pub fn serialize_xprv(&self) -> String {
+        let mut key_data = Vec::with_capacity(33);
+        key_data.push(0x00);
+        key_data.extend_from_slice(&self.key.serialize_sec1());
+        serialize_extended_key(
+            XPRV_VERSION,
+            self.depth,
+            self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &key_data,
+        )
+    }
+
+    /// Parses a key in the standard base58check `xprv` format.
+    pub fn deserialize_xprv(s: &str) -> Result<Self, ExtendedKeyError> {
+        let (depth, parent_fingerprint, child_number, chain_code, key_data) =
+            deserialize_extended_key(s, XPRV_VERSION)?;
+        if key_data[0] != 0x00 {
+            return Err(ExtendedKeyError::InvalidEncoding(
+                "private key data must be prefixed with 0x00".to_string(),
+            ));
+        }
+        Ok(Self {
+            key: PrivateKey::deserialize_sec1(&key_data[1..])?,
+            chain_code,
+            depth,
+            parent_fingerprint,
+            child_number,
+        })
+    }
+
+    /// Derives the child at `index`, which may be hardened
+    /// (`index >= HARDENED_OFFSET`) or not.
+    pub fn derive_child(&self, index: u32) -> Result<Self, ExtendedKeyError> {
+        let parent_compressed_pubkey = self.key.public_key().serialize_sec1(true);
+
+        let mut data = Vec::with_capacity(37);
+        if index >= HARDENED_OFFSET {
+            data.push(0x00);
+            data.extend_from_slice(&self.key.serialize_sec1());
+        } else {
+            data.extend_from_slice(&parent_compressed_pubkey);
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let (i_l, i_r) = hmac_sha512(&self.chain_code, &data);
+
+        let i_l_scalar = Option::<Scalar>::from(Scalar::from_repr(i_l.into()))
+            .ok_or(ExtendedKeyError::InvalidDerivedKey)?;
+        if bool::from(i_l_scalar.is_zero()) {
+            return Err(ExtendedKeyError::InvalidDerivedKey);
+        }
+
+        let child_secret = i_l_scalar + self.key.scalar();
+        if bool::from(child_secret.is_zero()) {
+            return Err(ExtendedKeyError::InvalidDerivedKey);
+        }
+
+        Ok(Self {
+            key: PrivateKey::deserialize_sec1(&child_secret.to_bytes())
+                .expect("a nonzero scalar is a valid secret key"),
+            chain_code: i_r,
+            depth: self.depth + 1,
+            parent_fingerprint: fingerprint(&parent_compressed_pubkey),
+            child_number: index,
+        })
+    }
+
+    /// Derives the descendant identified by `path`, e.g. `m/44'/223'/0'/0/0`.
+    pub fn derive_path(&self, path: &str) -> Result<Self, ExtendedKeyError> {
+        let mut key = self.clone();
+        for index in parse_path(path)? {
+            key = key.derive_child(index)?;
+        }
+        Ok(key)
+    }
+}
+
+impl ExtendedPublicKey {
+    /// The public key at this node of the tree.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.key
+    }
+
+    /// The chain code at this node of the tree.
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    /// Derives the non-hardened child at `index` using only public data,
+    /// via `child_pubkey = parent_pubkey + int(I_L)*G`. This matches the
+    /// key produced by `ExtendedPrivateKey::derive_child` at the same
+    /// index, but never requires the parent secret.
+    pub fn derive_child(&self, index: u32) -> Result<Self, ExtendedKeyError> {
+        if index >= HARDENED_OFFSET {
+            return Err(ExtendedKeyError::HardenedDerivationRequiresPrivateKey);
+        }
+
+        let parent_compressed_pubkey = self.key.serialize_sec1(true);
+
+        let mut data = Vec::with_capacity(37);
+        data.extend_from_slice(&parent_compressed_pubkey);
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let (i_l, i_r) = hmac_sha512(&self.chain_code, &data);
+
+        let i_l_scalar = Option::<Scalar>::from(Scalar::from_repr(i_l.into()))
+            .ok_or(ExtendedKeyError::InvalidDerivedKey)?;
+        if bool::from(i_l_scalar.is_zero()) {
+            return Err(ExtendedKeyError::InvalidDerivedKey);
+        }
+
+        let parent_point = ProjectivePoint::from(*self.key.as_k256().as_affine());
+        let child_point = parent_point + ProjectivePoint::GENERATOR * i_l_scalar;
+        if bool::from(k256::elliptic_curve::group::Group::is_identity(&child_point)) {
+            return Err(ExtendedKeyError::InvalidDerivedKey);
+        }
+
+        let child_point = child_point.to_affine();
+        let key = PublicKey::deserialize_sec1(child_point.to_encoded_point(true).as_bytes())?;
+
+        Ok(Self {
+            key,
+            chain_code: i_r,
+            depth: self.depth + 1,
+            parent_fingerprint: fingerprint(&parent_compressed_pubkey),
+            child_number: index,
+        })
+    }
+
+    /// Derives the descendant identified by `path`, e.g. `m/0/0/5`. Every
+    /// component of a public-only path must be non-hardened.
+    pub fn derive_path(&self, path: &str) -> Result<Self, ExtendedKeyError> {
+        let mut key = self.clone();
+        for index in parse_path(path)? {
+            key = key.derive_child(index)?;
+        }
+        Ok(key)
+    }
+
+    /// Serializes this key in the standard base58check `xpub` format.
+    pub fn serialize_xpub(&self) -> String {
+        serialize_extended_key(
+            XPUB_VERSION,
+            self.depth,
+            self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &self.key.serialize_sec1(true),
+        )
+    }
+
+    /// Parses a key in the standard base58check `xpub` format.
+    pub fn deserialize_xpub(s: &str) -> Result<Self, ExtendedKeyError> {
+        let (depth, parent_fingerprint, child_number, chain_code, key_data) =
+            deserialize_extended_key(s, XPUB_VERSION)?;
+        Ok(Self {
+            key: PublicKey::deserialize_sec1(&key_data)?,
+            chain_code,
+            depth,
+            parent_fingerprint,
+            child_number,
+        })
+    }
+}
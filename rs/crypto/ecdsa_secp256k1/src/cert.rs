@@ -0,0 +1,462 @@
+//! Self-signed X.509 v3 certificate generation from secp256k1 keys.
+//!
+//! This hand-rolls the small slice of ASN.1 DER that an X.509 certificate
+//! needs (rather than pulling in a general-purpose certificate-parsing
+//! crate) since a `CertificateBuilder` only ever needs to *emit* a fixed
+//! handful of structures: names, validity, `SubjectPublicKeyInfo`, and a
+//! few common extensions.
+
+use crate::{PrivateKey, PublicKey};
+
+// ---- Minimal ASN.1 DER encoding helpers -----------------------------------
+
+fn der_len(len: usize) -> Vec<u8> {
+    if len < 0x80 {
+        vec![len as u8]
+    } else {
+        let be = (len as u64).to_be_bytes();
+        let first_nonzero = be.iter().position(|b| *b != 0).unwrap_or(be.len() - 1);
+        let trimmed = &be[first_nonzero..];
+        let mut out = vec![0x80 | trimmed.len() as u8];
+        out.extend_from_slice(trimmed);
+        out
+    }
+}
+
+fn der_tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    out.extend(der_len(value.len()));
+    out.extend_from_slice(value);
+    out
+}
+
+fn der_sequence(value: &[u8]) -> Vec<u8> {
+    der_tlv(0x30, value)
+}
+
+fn der_set(value: &[u8]) -> Vec<u8> {
+    der_tlv(0x31, value)
+}
+
+fn der_boolean(value: bool) -> Vec<u8> {
+    der_tlv(0x01, &[if value { 0xff } else { 0x00 }])
+}
+
+fn der_integer_from_be_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut start = 0;
+    while start + 1 < bytes.len() && bytes[start] == 0 && bytes[start + 1] & 0x80 == 0 {
+        start += 1;
+    }
+    let mut v = bytes[start..].to_vec();
+    if v.is_empty() {
+        v.push(0);
+    }
+    if v[0] & 0x80 != 0 {
+        v.insert(0, 0x00);
+    }
+    der_tlv(0x02, &v)
+}
+
+fn der_integer_from_u64(n: u64) -> Vec<u8> {
+    der_integer_from_be_bytes(&n.to_be_bytes())
+}
+
+fn der_bitstring(bytes: &[u8], unused_bits: u8) -> Vec<u8> {
+    let mut v = vec![unused_bits];
+    v.extend_from_slice(bytes);
+    der_tlv(0x03, &v)
+}
+
+fn der_utf8_string(s: &str) -> Vec<u8> {
+    der_tlv(0x0c, s.as_bytes())
+}
+
+/// Encodes an absolute object identifier, e.g. `&[1, 2, 840, 10045, 2, 1]`.
+fn der_oid(arcs: &[u32]) -> Vec<u8> {
+    let mut body = vec![(arcs[0] * 40 + arcs[1]) as u8];
+    for &arc in &arcs[2..] {
+        if arc < 0x80 {
+            body.push(arc as u8);
+        } else {
+            let mut septets = Vec::new();
+            let mut n = arc;
+            septets.push((n & 0x7f) as u8);
+            n >>= 7;
+            while n > 0 {
+                septets.push(((n & 0x7f) as u8) | 0x80);
+                n >>= 7;
+            }
+            septets.reverse();
+            body.extend(septets);
+        }
+    }
+    der_tlv(0x06, &body)
+}
+
+const OID_EC_PUBLIC_KEY: &[u32] = &[1, 2, 840, 10045, 2, 1];
+const OID_SECP256K1: &[u32] = &[1, 3, 132, 0, 10];
+const OID_ECDSA_WITH_SHA256: &[u32] = &[1, 2, 840, 10045, 4, 3, 2];
+const OID_COMMON_NAME: &[u32] = &[2, 5, 4, 3];
+const OID_ORGANIZATION_NAME: &[u32] = &[2, 5, 4, 10];
+const OID_COUNTRY_NAME: &[u32] = &[2, 5, 4, 6];
+const OID_BASIC_CONSTRAINTS: &[u32] = &[2, 5, 29, 19];
+const OID_KEY_USAGE: &[u32] = &[2, 5, 29, 15];
+const OID_SUBJECT_ALT_NAME: &[u32] = &[2, 5, 29, 17];
+
+/// Converts Unix seconds since the epoch to a civil `(year, month, day,
+/// hour, minute, second)`, using Howard Hinnant's `civil_from_days`
+/// algorithm (proleptic Gregorian, valid for any year this type can hold).
+fn civil_from_unix(unix_secs: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (unix_secs / 86400) as i64;
+    let rem = unix_secs % 86400;
+    let (hour, minute, second) = ((rem / 3600) as u32, ((rem % 3600) / 60) as u32, (rem % 60) as u32);
+
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = (z - era * 146097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = if month <= 2 { y + 1 } else { y };
+
+    (year, month, day, hour, minute, second)
+}
+
+fn der_time(unix_secs: u64) -> Vec<u8> {
+    let (year, month, day, hour, minute, second) = civil_from_unix(unix_secs);
+    if (1950..2050).contains(&year) {
+        let s = format!(
+            "{:02}{:02}{:02}{:02}{:02}{:02}Z",
+            year % 100,
+            month,
+            day,
+            hour,
+            minute,
+            second
+        );
+        der_tlv(0x17, s.as_bytes())
+    } else {
+        let s = format!(
+            "{:04}{:02}{:02}{:02}{:02}{:02}Z",
+            year, month, day, hour, minute, second
+        );
+        der_tlv(0x18, s.as_bytes())
+    }
+}
+
+// ---- Public types ----------------------------------------------------------
+
+/// A (deliberately minimal) X.501 distinguished name, supporting the
+/// attributes that `openssl req` and most CA tooling populate by default.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DistinguishedName {
+    common_name: Option<String>,
+    organization: Option<String>,
+    country: Option<String>,
+}
+
+impl DistinguishedName {
+    /// Creates an empty distinguished name.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `commonName` (OID 2.5.4.3) attribute.
+    pub fn with_common_name(mut self, common_name: impl Into<String>) -> Self {
+        self.common_name = Some(common_name.into());
+        self
+    }
+
+    /// Sets the `organizationName` (OID 2.5.4.10) attribute.
+    pub fn with_organization(mut self, organization: impl Into<String>) -> Self {
+        self.organization = Some(organization.into());
+        self
+    }
+
+    /// Sets the `countryName` (OID 2.5.4.6) attribute.
+    pub fn with_country(mut self, country: impl Into<String>) -> Self {
+        self.country = Some(country.into());
+        self
+    }
+
+    fn to_der(&self) -> Vec<u8> {
+        let mut rdns = Vec::new();
+        if let Some(country) = &self.country {
+            rdns.extend(der_set(&der_sequence(
+                &[der_oid(OID_COUNTRY_NAME), der_tlv(0x13, country.as_bytes())].concat(),
+            )));
+        }
+        if let Some(organization) = &self.organization {
+            rdns.extend(der_set(&der_sequence(
+                &[der_oid(OID_ORGANIZATION_NAME), der_utf8_string(organization)].concat(),
+            )));
+        }
+        if let Some(common_name) = &self.common_name {
+            rdns.extend(der_set(&der_sequence(
+                &[der_oid(OID_COMMON_NAME), der_utf8_string(common_name)].concat(),
+            )));
+        }
+        der_sequence(&rdns)
+    }
+}
+
+/// The `basicConstraints` extension (OID 2.5.29.19).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BasicConstraints {
+    /// Whether this certificate may act as a CA.
+    pub ca: bool,
+    /// The maximum number of non-self-issued intermediate certificates
+    /// that may follow this one in a valid path, if `ca` is set.
+    pub path_len_constraint: Option<u32>,
+}
+
+impl BasicConstraints {
+    fn to_der(self) -> Vec<u8> {
+        let mut body = Vec::new();
+        if self.ca {
+            body.extend(der_boolean(true));
+            if let Some(path_len) = self.path_len_constraint {
+                body.extend(der_integer_from_u64(path_len as u64));
+            }
+        }
+        der_sequence(&body)
+    }
+}
+
+/// The subset of `keyUsage` (OID 2.5.29.15) bits this builder supports.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct KeyUsage {
+    pub digital_signature: bool,
+    pub key_cert_sign: bool,
+    pub crl_sign: bool,
+}
+
+impl KeyUsage {
+    fn to_der(self) -> Vec<u8> {
+        let mut byte = 0u8;
+        if self.digital_signature {
+            byte |= 0b1000_0000;
+        }
+        if self.key_cert_sign {
+            byte |= 0b0000_0100;
+        }
+        if self.crl_sign {
+            byte |= 0b0000_0010;
+        }
+        if byte == 0 {
+            // A bit string with no significant bits is encoded as the empty
+            // bit string per X.690; `der_bitstring(&[0], 7)` would instead
+            // produce a non-minimal encoding with a spurious content byte.
+            der_bitstring(&[], 0)
+        } else {
+            der_bitstring(&[byte], byte.trailing_zeros().min(7) as u8)
+        }
+    }
+}
+
+fn encode_extension(oid: &[u32], critical: bool, value: Vec<u8>) -> Vec<u8> {
+    let mut body = der_oid(oid);
+    if critical {
+        body.extend(der_boolean(true));
+    }
+    body.extend(der_tlv(0x04, &value));
+    der_sequence(&body)
+}
+
+fn encode_subject_alt_names(dns_names: &[String]) -> Vec<u8> {
+    let general_names: Vec<u8> = dns_names
+        .iter()
+        .flat_map(|name| der_tlv(0x82, name.as_bytes()))
+        .collect();
+    der_sequence(&general_names)
+}
+
+fn encode_spki(public_key: &PublicKey) -> Vec<u8> {
+    let algorithm = der_sequence(
+        &[der_oid(OID_EC_PUBLIC_KEY), der_oid(OID_SECP256K1)].concat(),
+    );
+    let subject_public_key = der_bitstring(&public_key.serialize_sec1(false), 0);
+    der_sequence(&[algorithm, subject_public_key].concat())
+}
+
+fn ecdsa_with_sha256_algorithm_identifier() -> Vec<u8> {
+    der_sequence(&der_oid(OID_ECDSA_WITH_SHA256))
+}
+
+/// Builds a self-signed X.509 v3 certificate.
+#[derive(Clone, Debug)]
+pub struct CertificateBuilder {
+    subject: DistinguishedName,
+    issuer: DistinguishedName,
+    serial_number: Vec<u8>,
+    not_before: u64,
+    not_after: u64,
+    basic_constraints: Option<BasicConstraints>,
+    key_usage: Option<KeyUsage>,
+    subject_alt_names: Vec<String>,
+}
+
+impl CertificateBuilder {
+    /// Creates a new builder. `not_before`/`not_after` are Unix timestamps
+    /// (seconds); `serial_number` is the certificate's serial as a
+    /// big-endian byte string.
+    pub fn new(
+        subject: DistinguishedName,
+        issuer: DistinguishedName,
+        serial_number: Vec<u8>,
+        not_before: u64,
+        not_after: u64,
+    ) -> Self {
+        Self {
+            subject,
+            issuer,
+            serial_number,
+            not_before,
+            not_after,
+            basic_constraints: None,
+            key_usage: None,
+            subject_alt_names: Vec::new(),
+        }
+    }
+
+    /// Adds a `basicConstraints` extension.
+    pub fn with_basic_constraints(mut self, basic_constraints: BasicConstraints) -> Self {
+        self.basic_constraints = Some(basic_constraints);
+        self
+    }
+
+    /// Adds a `keyUsage` extension.
+    pub fn with_key_usage(mut self, key_usage: KeyUsage) -> Self {
+        self.key_usage = Some(key_usage);
+        self
+    }
+
+    /// Adds a `subjectAltName` extension containing the given DNS names.
+    pub fn with_subject_alt_names(mut self, dns_names: Vec<String>) -> Self {
+        self.subject_alt_names = dns_names;
+        self
+    }
+
+    fn extensions_der(&self) -> Option<Vec<u8>> {
+        let mut extensions = Vec::new();
+        if let Some(basic_constraints) = self.basic_constraints {
+            extensions.extend(encode_extension(
+                OID_BASIC_CONSTRAINTS,
+                true,
+                basic_constraints.to_der(),
+            ));
+        }
+        if let Some(key_usage) = self.key_usage {
+            extensions.extend(encode_extension(OID_KEY_USAGE, true, key_usage.to_der()));
+        }
+        if !self.subject_alt_names.is_empty() {
+            extensions.extend(encode_extension(
+                OID_SUBJECT_ALT_NAME,
+                false,
+                encode_subject_alt_names(&self.subject_alt_names),
+            ));
+        }
+
+        if extensions.is_empty() {
+            None
+        } else {
+            // `[3] EXPLICIT Extensions`
+            Some(der_tlv(0xa3, &der_sequence(&extensions)))
+        }
+    }
+
+    /// Builds the `TBSCertificate` and signs it with `key`'s private key
+    /// using ECDSA with SHA-256, producing a self-signed certificate whose
+    /// `SubjectPublicKeyInfo` is `key`'s public key.
+    pub fn build_and_sign(self, key: &PrivateKey) -> Certificate {
+        let signature_algorithm = ecdsa_with_sha256_algorithm_identifier();
+
+        let mut tbs_body = Vec::new();
+        tbs_body.extend(der_tlv(0xa0, &der_integer_from_u64(2))); // version: v3
+        tbs_body.extend(der_integer_from_be_bytes(&self.serial_number));
+        tbs_body.extend(signature_algorithm.clone());
+        tbs_body.extend(self.issuer.to_der());
+        tbs_body.extend(der_sequence(
+            &[der_time(self.not_before), der_time(self.not_after)].concat(),
+        ));
+        tbs_body.extend(self.subject.to_der());
+        let spki = encode_spki(&key.public_key());
+        tbs_body.extend(spki.clone());
+        if let Some(extensions) = self.extensions_der() {
+            tbs_body.extend(extensions);
+        }
+
+        let tbs_certificate = der_sequence(&tbs_body);
+        let signature = key.sign_message_der(&tbs_certificate);
+
+        let certificate = der_sequence(
+            &[
+                tbs_certificate.clone(),
+                signature_algorithm,
+                der_bitstring(&signature, 0),
+            ]
+            .concat(),
+        );
+
+        Certificate {
+            der: certificate,
+            tbs_certificate_der: tbs_certificate,
+            subject_public_key_info_der: spki,
+            signature,
+        }
+    }
+}
+
+/// A signed X.509 v3 certificate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Certificate {
+    der: Vec<u8>,
+    tbs_certificate_der: Vec<u8>,
+    subject_public_key_info_der: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl Certificate {
+    /// The full DER encoding of this certificate.
+    pub fn serialize_der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// The PEM encoding of this certificate (`-----BEGIN CERTIFICATE-----`).
+    pub fn serialize_pem(&self) -> String {
+        use base64::Engine;
+        let body = base64::engine::general_purpose::STANDARD.encode(&self.der);
+        let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+        for line in body.as_bytes().chunks(64) {
+            pem.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+            pem.push('\n');
+        }
+        pem.push_str("-----END CERTIFICATE-----\n");
+        pem
+    }
+
+    /// The DER encoding of this certificate's `TBSCertificate`, i.e. the
+    /// bytes `signature` is computed over.
+    pub fn tbs_certificate_der(&self) -> &[u8] {
+        &self.tbs_certificate_der
+    }
+
+    /// The DER-encoded ECDSA signature over `tbs_certificate_der`.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// The DER encoding of this certificate's `SubjectPublicKeyInfo`,
+    /// parseable with `PublicKey::deserialize_der`.
+    pub fn subject_public_key_info_der(&self) -> &[u8] {
+        &self.subject_public_key_info_der
+    }
+
+    /// Verifies that this certificate was signed by `issuer`.
+    pub fn verify_signature(&self, issuer: &PublicKey) -> bool {
+        issuer.verify_signature_der(&self.tbs_certificate_der, &self.signature)
+    }
+}
@@ -0,0 +1,131 @@
+//! JWK (JSON Web Key, RFC 7517) import/export for secp256k1 keys, for
+//! interoperating with JOSE/JWT and verifiable-credential tooling.
+
+use crate::{KeyDecodingError, PrivateKey, PublicKey};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+const CURVE_NAME: &str = "secp256k1";
+
+fn b64url_encode(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn b64url_decode(s: &str) -> Result<Vec<u8>, KeyDecodingError> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(s)
+        .map_err(|e| KeyDecodingError::InvalidKeyEncoding(e.to_string()))
+}
+
+fn fixed_32_bytes(bytes: Vec<u8>, field: &str) -> Result<[u8; 32], KeyDecodingError> {
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        KeyDecodingError::InvalidKeyEncoding(format!(
+            "JWK field `{}` must be 32 bytes, was {}",
+            field,
+            bytes.len()
+        ))
+    })
+}
+
+/// The standard EC JWK representation of a secp256k1 public key, i.e.
+/// `{"kty":"EC","crv":"secp256k1","x":...,"y":...}`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PublicKeyJwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    pub y: String,
+}
+
+/// The standard EC JWK representation of a secp256k1 private key, which
+/// adds the `d` field to `PublicKeyJwk`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PrivateKeyJwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    pub y: String,
+    pub d: String,
+}
+
+impl PublicKey {
+    /// Exports this key as an EC JWK.
+    pub fn to_jwk(&self) -> PublicKeyJwk {
+        let uncompressed = self.serialize_sec1(false);
+        debug_assert_eq!(uncompressed.len(), 65);
+
+        PublicKeyJwk {
+            kty: "EC".to_string(),
+            crv: CURVE_NAME.to_string(),
+            x: b64url_encode(&uncompressed[1..33]),
+            y: b64url_encode(&uncompressed[33..65]),
+        }
+    }
+
+    /// Imports a public key from an EC JWK, validating that `crv` is
+    /// `secp256k1`, that `x`/`y` decode to 32 bytes each, and that the
+    /// resulting point lies on the curve.
+    pub fn from_jwk(jwk: &PublicKeyJwk) -> Result<Self, KeyDecodingError> {
+        if jwk.kty != "EC" {
+            return Err(KeyDecodingError::InvalidKeyEncoding(format!(
+                "unsupported kty `{}`, expected `EC`",
+                jwk.kty
+            )));
+        }
+        if jwk.crv != CURVE_NAME {
+            return Err(KeyDecodingError::InvalidKeyEncoding(format!(
+                "unsupported crv `{}`, expected `{}`",
+                jwk.crv, CURVE_NAME
+            )));
+        }
+
+        let x = fixed_32_bytes(b64url_decode(&jwk.x)?, "x")?;
+        let y = fixed_32_bytes(b64url_decode(&jwk.y)?, "y")?;
+
+        let mut uncompressed = [0u8; 65];
+        uncompressed[0] = 0x04;
+        uncompressed[1..33].copy_from_slice(&x);
+        uncompressed[33..65].copy_from_slice(&y);
+
+        Self::deserialize_sec1(&uncompressed)
+    }
+}
+
+impl PrivateKey {
+    /// Exports this key as an EC JWK, including the private scalar `d`.
+    pub fn to_jwk(&self) -> PrivateKeyJwk {
+        let public_jwk = self.public_key().to_jwk();
+
+        PrivateKeyJwk {
+            kty: public_jwk.kty,
+            crv: public_jwk.crv,
+            x: public_jwk.x,
+            y: public_jwk.y,
+            d: b64url_encode(&self.serialize_sec1()),
+        }
+    }
+
+    /// Imports a private key from an EC JWK, validating it the same way as
+    /// `PublicKey::from_jwk` and additionally checking that `d` derives the
+    /// encoded `(x, y)`.
+    pub fn from_jwk(jwk: &PrivateKeyJwk) -> Result<Self, KeyDecodingError> {
+        let public_jwk = PublicKeyJwk {
+            kty: jwk.kty.clone(),
+            crv: jwk.crv.clone(),
+            x: jwk.x.clone(),
+            y: jwk.y.clone(),
+        };
+        let expected_public_key = PublicKey::from_jwk(&public_jwk)?;
+
+        let d = fixed_32_bytes(b64url_decode(&jwk.d)?, "d")?;
+        let key = Self::deserialize_sec1(&d)?;
+
+        if key.public_key() != expected_public_key {
+            return Err(KeyDecodingError::InvalidKeyEncoding(
+                "JWK `d` does not correspond to the JWK `(x, y)`".to_string(),
+            ));
+        }
+
+        Ok(key)
+    }
+}
@@ -0,0 +1,89 @@
+//! Recoverable ECDSA signatures: recovering the signer's public key from a
+//! signature and message, as used by Ethereum-style protocols that would
+//! otherwise need to carry the public key alongside the signature.
+
+use crate::{PrivateKey, PublicKey};
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+/// A 64-byte `r || s` ECDSA signature together with the recovery id needed
+/// to recover the signer's public key from it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RecoverableSignature {
+    /// The 64-byte `r || s` signature, with `s` normalized to the lower
+    /// half of the curve order.
+    pub signature: [u8; 64],
+    /// Which of the (up to) four candidate points was used: bit 0 selects
+    /// the y-parity of `R`, bit 1 selects whether `r` wrapped past the
+    /// curve order.
+    pub recovery_id: u8,
+}
+
+/// Errors that can occur while recovering a public key from a signature.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RecoveryError {
+    /// `signature` was not a valid 64-byte `r || s` encoding.
+    InvalidSignatureEncoding,
+    /// `recovery_id` was not in `0..=3`.
+    InvalidRecoveryId,
+    /// No point on the curve corresponds to the given `(signature, recovery_id)`.
+    NoMatchingPublicKey,
+}
+
+impl std::fmt::Display for RecoveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSignatureEncoding => write!(f, "invalid signature encoding"),
+            Self::InvalidRecoveryId => write!(f, "recovery id must be in 0..=3"),
+            Self::NoMatchingPublicKey => {
+                write!(f, "no public key recovers to the given signature")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecoveryError {}
+
+impl PrivateKey {
+    /// Signs `message` as in `sign_message`, additionally returning the
+    /// recovery id needed to recover this key's public key from the
+    /// signature alone.
+    pub fn sign_message_recoverable(&self, message: &[u8]) -> RecoverableSignature {
+        let digest = Sha256::digest(message);
+        let (sig, recid): (Signature, RecoveryId) = self
+            .signing_key()
+            .sign_prehash(&digest)
+            .expect("signing with a valid key over a fixed-size digest cannot fail");
+
+        let mut signature = [0u8; 64];
+        signature.copy_from_slice(&sig.to_bytes());
+
+        RecoverableSignature {
+            signature,
+            recovery_id: recid.to_byte(),
+        }
+    }
+}
+
+impl PublicKey {
+    /// Recovers the public key that produced `signature` (with the given
+    /// `recovery_id`) over `message`, returning an error if no point on the
+    /// curve matches.
+    pub fn recover(
+        message: &[u8],
+        signature: &[u8],
+        recovery_id: u8,
+    ) -> Result<Self, RecoveryError> {
+        let sig =
+            Signature::from_slice(signature).map_err(|_| RecoveryError::InvalidSignatureEncoding)?;
+        let recid =
+            RecoveryId::from_byte(recovery_id).ok_or(RecoveryError::InvalidRecoveryId)?;
+
+        let digest = Sha256::digest(message);
+        let key = VerifyingKey::recover_from_prehash(&digest, &sig, recid)
+            .map_err(|_| RecoveryError::NoMatchingPublicKey)?;
+
+        Ok(Self { key })
+    }
+}